@@ -4,13 +4,13 @@ use std::str::FromStr;
 
 use poise::serenity_prelude::GatewayIntents;
 use poise::{async_trait, CreateReply};
-use protocol::VersionResponse;
+use protocol::{RenderOptions, VersionResponse, MAX_MIN_SIZE, POINTS_PER_INCH};
 use rusqlite::{named_params, Connection, OpenFlags};
 use serenity::builder::{CreateAllowedMentions, CreateAttachment};
 use tokio::join;
 use tokio::sync::{mpsc, Mutex};
 
-use crate::worker::Worker;
+use crate::worker::{Timeout, Worker};
 use crate::SOURCE_URL;
 
 /// U+200D is a zero-width joiner.
@@ -117,13 +117,22 @@ impl PageSize {
 struct Preamble {
 	page_size: PageSize,
 	theme: Theme,
+	numbering: bool,
 }
 
 impl Preamble {
 	fn preamble(self) -> String {
 		let page_size = self.page_size.preamble();
 		let theme = self.theme.preamble();
-		if theme.is_empty() && page_size.is_empty() {
+		let numbering = if self.numbering {
+			concat!(
+				"// Numbering:\n",
+				"#set math.equation(numbering: \"(1)\")\n",
+			)
+		} else {
+			""
+		};
+		if theme.is_empty() && page_size.is_empty() && numbering.is_empty() {
 			String::new()
 		} else {
 			format!(
@@ -133,15 +142,38 @@ impl Preamble {
 					"{page_size}",
 					"// Theme:\n",
 					"{theme}",
+					"{numbering}",
 					"// End preamble\n",
 				),
 				page_size = page_size,
 				theme = theme,
+				numbering = numbering,
 			)
 		}
 	}
 }
 
+#[test]
+fn test_preamble_numbering() {
+	let default = Preamble::default().preamble();
+	assert!(!default.contains("numbering"));
+
+	let numbered = Preamble {
+		numbering: true,
+		..Preamble::default()
+	}
+	.preamble();
+	assert!(numbered.contains("#set math.equation(numbering: \"(1)\")\n"));
+
+	let only_numbering = Preamble {
+		page_size: PageSize::Default,
+		theme: Theme::Transparent,
+		numbering: true,
+	}
+	.preamble();
+	assert!(only_numbering.starts_with("// Begin preamble\n"));
+}
+
 struct Data {
 	pool: Mutex<Worker>,
 	database: std::sync::Mutex<Connection>,
@@ -150,9 +182,24 @@ struct Data {
 type PoiseError = Box<dyn std::error::Error + Send + Sync + 'static>;
 type Context<'a> = poise::Context<'a, Data, PoiseError>;
 
+/// Parses a size like `200x100`.
+fn parse_size(raw: &str) -> Option<(u32, u32)> {
+	let (width, height) = raw.split_once('x')?;
+	Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+#[test]
+fn test_parse_size() {
+	assert_eq!(parse_size("200x100"), Some((200, 100)));
+	assert_eq!(parse_size("200"), None);
+	assert_eq!(parse_size("200x"), None);
+	assert_eq!(parse_size("ax1"), None);
+}
+
 #[derive(Debug, Default)]
 struct RenderFlags {
 	preamble: Preamble,
+	options: RenderOptions,
 }
 
 #[async_trait]
@@ -174,6 +221,51 @@ impl<'a> poise::PopArgument<'a> for RenderFlags {
 					"pagesize" | "ps" => {
 						parsed.preamble.page_size = value.parse().map_err(|_| "invalid page size")?;
 					}
+					"numbering" | "n" => {
+						parsed.preamble.numbering = value.parse().map_err(|_| "invalid numbering value")?;
+					}
+					"snap" => {
+						parsed.options.snap_scale = value.parse().map_err(|_| "invalid snap value")?;
+					}
+					"minsize" | "ms" => {
+						let (width, height) = parse_size(value).ok_or("invalid minimum size")?;
+						if width > MAX_MIN_SIZE || height > MAX_MIN_SIZE {
+							return Err(
+								format!("minimum size can be at most {MAX_MIN_SIZE}x{MAX_MIN_SIZE}").into(),
+							);
+						}
+						parsed.options.min_size = Some((width, height));
+					}
+					"minedge" | "me" => {
+						let min_long_edge = value.parse().map_err(|_| "invalid minimum edge")?;
+						if min_long_edge > MAX_MIN_SIZE {
+							return Err(format!("minimum edge can be at most {MAX_MIN_SIZE}").into());
+						}
+						parsed.options.min_long_edge = Some(min_long_edge);
+					}
+					"dpi" => {
+						let dpi = if value == "native" {
+							POINTS_PER_INCH
+						} else {
+							value.parse::<f32>().map_err(|_| "invalid dpi")?
+						};
+						if dpi.is_finite() && dpi > 0.0 {
+							parsed.options.dpi = Some(dpi);
+						} else {
+							return Err("dpi must be positive".into());
+						}
+					}
+					"page" | "p" => {
+						let page = value.parse::<usize>().map_err(|_| "invalid page")?;
+						parsed.options.first_page = page.checked_sub(1).ok_or("pages start at 1")?;
+					}
+					"strictfonts" | "sf" => {
+						parsed.options.strict_fonts = value.parse().map_err(|_| "invalid strictfonts value")?;
+					}
+					"strict" => {
+						parsed.options.warnings_as_errors =
+							value.parse().map_err(|_| "invalid strict value")?;
+					}
 					_ => {
 						return Err(format!("unrecognized flag {key:?}").into());
 					}
@@ -199,11 +291,19 @@ fn render_help() -> String {
 		"\
 Render Typst code as an image.
 
-Syntax: `?render [pagesize=<page size>] [theme=<theme>] <code block> [...]`
+Syntax: `?render [<flag>=<value> ...] <code block> [...]`
 
 **Flags**
 - `pagesize` can be `preview` (default),  `auto`, or `default`.
 - `theme` can be `dark` (default), `light`, or `transparent`.
+- `numbering` can be `true` or `false` (default). If enabled, equations are numbered.
+- `snap` can be `true` or `false` (default). If enabled, the scale is rounded up to whole pixels per point, which makes small text crisper.
+- `minsize` pads the image to at least the given size in pixels, e.g. `200x200`, up to 2000 pixels per side. This helps with tiny snippets.
+- `minedge` scales the content up until the longer side of the image is at least the given number of pixels, up to 2000.
+- `dpi` renders at exactly the given resolution, e.g. `300` for print, or `native` for one pixel per point. This overrides `snap` and `minedge`.
+- `page` is the first page to render, starting at 1 (default).
+- `strictfonts` can be `true` or `false` (default). If enabled, using an unavailable font is an error instead of a warning.
+- `strict` can be `true` or `false` (default). If enabled, any warning is an error.
 
 To be clear, the full default preamble is:
 ```
@@ -228,6 +328,92 @@ And some text.
 	)
 }
 
+/// Removes a Markdown code fence that surrounds the whole input, if it is tagged as Typst or untagged.
+///
+/// Fences tagged with other languages are left alone, since they are valid Typst raw blocks.
+/// So are single-line spans like ```` ```$x$``` ````: as in Markdown, the input only counts as
+/// fenced if the opening fence ends its line and the closing fence is on a line of its own.
+/// Returns the input unchanged if it is not fenced.
+fn strip_code_fence(input: &str) -> &str {
+	let trimmed = input.trim();
+	let fence_len = trimmed.len() - trimmed.trim_start_matches('`').len();
+	if fence_len < 3 {
+		return input;
+	}
+	let fence = &trimmed[..fence_len];
+
+	let Some(inner) = trimmed[fence_len..]
+		.strip_suffix(fence)
+		// The closing fence must be exactly as long as the opening one.
+		.filter(|inner| !inner.ends_with('`'))
+		// Two separate blocks, not one surrounding block.
+		.filter(|inner| !inner.contains(fence))
+	else {
+		return input;
+	};
+
+	// Like Typst, take the tag to be the identifier right after the fence, but allow leading spaces.
+	let inner = inner.trim_start_matches([' ', '\t']);
+	let is_tag_char = |ch: char| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '+');
+	let tag_len = inner.find(|ch| !is_tag_char(ch)).unwrap_or(inner.len());
+	let (tag, body) = inner.split_at(tag_len);
+	if !(tag.is_empty() || tag.eq_ignore_ascii_case("typ") || tag.eq_ignore_ascii_case("typst")) {
+		return input;
+	}
+
+	// The rest of the tag line must be blank. This also drops a CRLF line ending.
+	let after_tag = body.trim_start_matches([' ', '\t']);
+	let Some(content) = after_tag
+		.strip_prefix("\r\n")
+		.or_else(|| after_tag.strip_prefix('\n'))
+	else {
+		return input;
+	};
+
+	let content = content.trim_end_matches([' ', '\t']);
+	if !(content.is_empty() || content.ends_with('\n')) {
+		return input;
+	}
+	content
+}
+
+#[test]
+fn test_strip_code_fence() {
+	assert_eq!(strip_code_fence("```\nhello\n```"), "hello\n");
+	assert_eq!(
+		strip_code_fence("```typst\n= Heading\n```\n"),
+		"= Heading\n"
+	);
+	assert_eq!(strip_code_fence("```typst\r\n= Hi\r\n```"), "= Hi\r\n");
+	assert_eq!(strip_code_fence("``` typst\n= Hi\n```"), "= Hi\n");
+	assert_eq!(strip_code_fence("````\nx\n````"), "x\n");
+	assert_eq!(
+		strip_code_fence("````typ\n```rust a```\n````"),
+		"```rust a```\n"
+	);
+	assert_eq!(strip_code_fence("````\nx\n```"), "````\nx\n```");
+	assert_eq!(strip_code_fence("```\nx\n````"), "```\nx\n````");
+	assert_eq!(
+		strip_code_fence("```rust\nfn main() {}\n```"),
+		"```rust\nfn main() {}\n```"
+	);
+	assert_eq!(strip_code_fence("```$x$```"), "```$x$```");
+	assert_eq!(
+		strip_code_fence("```= Heading\nbody```"),
+		"```= Heading\nbody```"
+	);
+	assert_eq!(strip_code_fence("```\nbody```"), "```\nbody```");
+	assert_eq!(strip_code_fence("```\nbody\n  ```"), "body\n");
+	assert_eq!(strip_code_fence("```\n```"), "");
+	assert_eq!(strip_code_fence("no fence"), "no fence");
+	assert_eq!(strip_code_fence("```a``` and ```b"), "```a``` and ```b");
+	assert_eq!(
+		strip_code_fence("```a``` and ```b```"),
+		"```a``` and ```b```"
+	);
+	assert_eq!(strip_code_fence("```"), "```");
+}
+
 /// Extracts the contents of a code block.
 ///
 /// If the language is `ansi`, then ANSI escape codes will be stripped from the input.
@@ -251,11 +437,21 @@ impl<'a> poise::PopArgument<'a> for CodeBlock {
 			poise::prefix_argument::CodeBlock::pop_from(args, attachment_index, ctx, message).await?;
 
 		let mut source = code_block.code;
+		// The worker strips leading BOMs too, but it only sees the source after the preamble has been
+		// prepended, so a BOM from the user's code would end up in the middle. Strip it here as well.
+		let stripped = source.trim_start_matches('\u{feff}');
+		if stripped.len() != source.len() {
+			source = stripped.to_owned();
+		}
 		// Strip ANSI escapes if provided.
 		if code_block.language.as_deref() == Some("ansi") {
 			source = strip_ansi_escapes::strip_str(source);
 		}
 
+		// Strip a literal fence, e.g. if the code was pasted from a Markdown file with its fence.
+		// This must happen before unescaping below, since escaped fences are Typst raw blocks.
+		source = strip_code_fence(&source).to_owned();
+
 		// Remove all occurrences of zero width joiners surrounded by backticks.
 		// This is used to enter Typst code blocks within Discord-markdown code blocks.
 		// Two replace calls are needed to remove all patterns of `ABA`: ABABABA => AABAA => AAAA.
@@ -301,12 +497,13 @@ async fn render(
 
 	let mut source = code.source;
 	source.insert_str(0, &flags.preamble.preamble());
+	let first_page = flags.options.first_page;
 
 	let mut progress = String::new();
 	let (progress_send, mut progress_recv) = mpsc::channel(4);
 	let (res, ()) = {
 		let mut pool = pool.lock().await;
-		join!(pool.render(source, progress_send), async {
+		join!(pool.render(source, flags.options, progress_send), async {
 			// When `render` finishes, it will drop the sender so this loop will finish.
 			while let Some(item) = progress_recv.recv().await {
 				progress.reserve(item.len() + 1);
@@ -352,17 +549,25 @@ async fn render(
 			}
 
 			for (i, image) in res.images.into_iter().enumerate() {
-				let image = CreateAttachment::bytes(image, format!("page-{}.png", i + 1));
+				let page = first_page + i + 1;
+				let image = CreateAttachment::bytes(image, format!("page-{page}.png"));
 				message = message.attachment(image);
 			}
 
 			ctx.send(message).await?;
 		}
 		Err(error) => {
-			let message = format!(
-				"An error occurred:\n```ansi\n{}\n```",
-				sanitize_code_block(&format!("{error:?}")),
-			);
+			let message = if let Some(Timeout { elapsed }) = error.downcast_ref() {
+				format!(
+					"Rendering timed out after {} seconds. Try simplifying your document.",
+					elapsed.as_secs(),
+				)
+			} else {
+				format!(
+					"An error occurred:\n```ansi\n{}\n```",
+					sanitize_code_block(&format!("{error:?}")),
+				)
+			};
 			ctx.reply(message).await?;
 		}
 	}
@@ -3,11 +3,32 @@ use std::process::{Child, Stdio};
 use std::time::Duration;
 
 use anyhow::{anyhow, bail, Context as _};
-use protocol::{Request, Response};
+use protocol::{RenderOptions, Request, Response};
 use tokio::select;
 use tokio::sync::mpsc;
 use tokio::time::Instant;
 
+/// Returned (through `anyhow`) when the worker did not respond in time.
+///
+/// Use `downcast_ref` to distinguish this from other failures.
+#[derive(Debug, thiserror::Error)]
+#[error("timed out after {elapsed:?}")]
+pub struct Timeout {
+	pub elapsed: Duration,
+}
+
+#[test]
+fn test_timeout_downcast() {
+	let error = anyhow::Error::from(Timeout {
+		elapsed: Duration::from_secs(5),
+	});
+	assert!(matches!(
+		error.downcast_ref::<Timeout>(),
+		Some(Timeout { elapsed }) if *elapsed == Duration::from_secs(5),
+	));
+	assert_eq!(error.to_string(), "timed out after 5s");
+}
+
 #[derive(Debug)]
 pub struct Worker {
 	process: Process,
@@ -25,7 +46,7 @@ impl Worker {
 		request: Request,
 		progress_channel_outer: Option<mpsc::Sender<String>>,
 	) -> anyhow::Result<Response> {
-		struct Timeout;
+		let start = Instant::now();
 
 		// This timeout is reset any time a progress message is received.
 		let fast_timeout = Duration::from_secs(5);
@@ -54,10 +75,10 @@ impl Worker {
 							}
 						}
 						() = fast_timeout_fut.as_mut() => {
-							break Err(Timeout);
+							break Err(Timeout { elapsed: start.elapsed() });
 						}
 						() = long_timeout_fut.as_mut() => {
-							break Err(Timeout);
+							break Err(Timeout { elapsed: start.elapsed() });
 						}
 					};
 				}
@@ -69,9 +90,9 @@ impl Worker {
 					self.process.replace().await?;
 					error
 				}
-				Err(Timeout) => {
+				Err(timeout) => {
 					self.process.replace().await?;
-					bail!("timeout");
+					return Err(timeout.into());
 				}
 			};
 
@@ -85,10 +106,11 @@ impl Worker {
 	pub async fn render(
 		&mut self,
 		code: String,
+		options: RenderOptions,
 		progress_channel: mpsc::Sender<String>,
 	) -> anyhow::Result<protocol::Rendered> {
 		let response = self
-			.run(Request::Render { code }, Some(progress_channel))
+			.run(Request::Render { code, options }, Some(progress_channel))
 			.await?;
 		let Response::Render(response) = response else {
 			bail!("expected Render response, got {response:?}");
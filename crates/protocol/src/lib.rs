@@ -1,15 +1,46 @@
 use serde::{Deserialize, Serialize};
 
+/// A DPI of this renders one pixel per point.
+pub const POINTS_PER_INCH: f32 = 72.0;
+
+/// The largest size in pixels accepted for `min_size` and `min_long_edge`.
+pub const MAX_MIN_SIZE: u32 = 2000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RenderOptions {
+	/// Render at exactly this many dots per inch instead of picking a scale automatically.
+	/// The other scale options are ignored if this is set.
+	pub dpi: Option<f32>,
+	/// Round the scale up to a whole number of pixels per point so small text lands on the pixel grid.
+	pub snap_scale: bool,
+	/// Pad the output to at least this many pixels (width, height), centering the content.
+	pub min_size: Option<(u32, u32)>,
+	/// Scale the content up until the longer side of the page has at least this many pixels.
+	pub min_long_edge: Option<u32>,
+	/// Fail instead of warning when a requested font family is not available.
+	pub strict_fonts: bool,
+	/// Fail if compilation produces any warnings.
+	pub warnings_as_errors: bool,
+	/// The index of the first page to render.
+	pub first_page: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Request {
-	Render { code: String },
-	Ast { code: String },
+	Render {
+		code: String,
+		options: RenderOptions,
+	},
+	Ast {
+		code: String,
+	},
 	Version,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Rendered {
 	pub images: Vec<Vec<u8>>,
+	/// The number of pages after the rendered ones.
 	pub more_pages: usize,
 	pub warnings: String,
 }
@@ -141,6 +141,27 @@ fn severity_to_report_kind(severity: typst::diag::Severity) -> ariadne::ReportKi
 	}
 }
 
+/// The name shown for a file in reports, e.g. `main.typ` or `@preview/example:0.1.0/lib.typ`.
+fn file_name(id: FileId) -> String {
+	let path = id.vpath().as_rootless_path().display();
+	match id.package() {
+		Some(package) => format!("{package}/{path}"),
+		None => path.to_string(),
+	}
+}
+
+#[test]
+fn test_file_name() {
+	use typst::syntax::VirtualPath;
+
+	let main = FileId::new(None, VirtualPath::new("main.typ"));
+	assert_eq!(file_name(main), "main.typ");
+
+	let package = "@preview/example:0.1.0".parse().unwrap();
+	let included = FileId::new(Some(package), VirtualPath::new("src/lib.typ"));
+	assert_eq!(file_name(included), "@preview/example:0.1.0/src/lib.typ");
+}
+
 struct SourceCache<'a> {
 	sandbox: &'a WithSource<'a>,
 	cache: HashMap<FileId, ariadne::Source>,
@@ -174,7 +195,7 @@ impl Cache<FileId> for SourceCache<'_> {
 	}
 
 	fn display<'a>(&self, id: &'a FileId) -> Option<Box<dyn std::fmt::Display + 'a>> {
-		Some(Box::new(format!("{id:?}")))
+		Some(Box::new(file_name(*id)))
 	}
 }
 
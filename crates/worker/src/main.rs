@@ -33,6 +33,7 @@ fn write_progress(msg: String) {
 
 fn main() {
 	let sandbox = Sandbox::new();
+	sandbox.warm();
 
 	loop {
 		let res = bincode::deserialize_from(std::io::stdin().lock());
@@ -48,10 +49,15 @@ fn main() {
 		let request: Request = res.unwrap();
 
 		let response = match request {
-			Request::Render { code } => {
-				let response = std::panic::catch_unwind(AssertUnwindSafe(|| render(&sandbox, code)));
+			Request::Render { code, options } => {
+				let response =
+					std::panic::catch_unwind(AssertUnwindSafe(|| render(&sandbox, code, &options)));
 				let response = response
-					.map_err(|panic| panic_to_string(&*panic))
+					.map_err(|panic| {
+						// The panic may have left the caches inconsistent or poisoned.
+						sandbox.reset();
+						panic_to_string(&*panic)
+					})
 					.and_then(|inner| inner);
 				Response::Render(response)
 			}
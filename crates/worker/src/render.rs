@@ -1,7 +1,10 @@
 use std::io::Cursor;
 
-use protocol::Rendered;
-use typst::layout::{Axis, Size};
+use protocol::{RenderOptions, Rendered, MAX_MIN_SIZE, POINTS_PER_INCH};
+use tiny_skia::{ColorU8, Pixmap, PremultipliedColorU8};
+use typst::diag::{Severity, SourceDiagnostic};
+use typst::layout::{Abs, Size};
+use typst::visualize::Paint;
 
 use crate::diagnostic::format_diagnostics;
 use crate::sandbox::Sandbox;
@@ -9,16 +12,21 @@ use crate::sandbox::Sandbox;
 const DESIRED_RESOLUTION: f32 = 1000.0;
 const MAX_SIZE: f32 = 10000.0;
 const MAX_PIXELS_PER_POINT: f32 = 5.0;
+/// Only enforced for fixed DPI and padded canvases, since the automatic scale already targets a
+/// fixed resolution.
+const MAX_PIXELS: f32 = 5000.0 * 5000.0;
 
 #[derive(Debug, thiserror::Error)]
 #[error(
-	"rendered output was too big: the {axis:?} axis was {size} pt but the maximum is {MAX_SIZE}"
+	"rendered output was too big: the page was {x} pt by {y} pt but the maximum is {MAX_SIZE} pt on each axis"
 )]
 pub struct TooBig {
-	size: f32,
-	axis: Axis,
+	x: f32,
+	y: f32,
 }
 
+/// Picks a scale such that the page has roughly `DESIRED_RESOLUTION` pixels along each axis
+/// (`DESIRED_RESOLUTION` squared in total), or fails if either side exceeds `MAX_SIZE` points.
 fn determine_pixels_per_point(size: Size) -> Result<f32, TooBig> {
 	// We want to truncate.
 	#![allow(clippy::cast_possible_truncation)]
@@ -26,16 +34,8 @@ fn determine_pixels_per_point(size: Size) -> Result<f32, TooBig> {
 	let x = size.x.to_pt() as f32;
 	let y = size.y.to_pt() as f32;
 
-	if x > MAX_SIZE {
-		Err(TooBig {
-			size: x,
-			axis: Axis::X,
-		})
-	} else if y > MAX_SIZE {
-		Err(TooBig {
-			size: y,
-			axis: Axis::Y,
-		})
+	if x > MAX_SIZE || y > MAX_SIZE {
+		Err(TooBig { x, y })
 	} else {
 		let area = x * y;
 		let nominal = DESIRED_RESOLUTION / area.sqrt();
@@ -43,14 +43,385 @@ fn determine_pixels_per_point(size: Size) -> Result<f32, TooBig> {
 	}
 }
 
+#[test]
+fn test_determine_pixels_per_point() {
+	let normal = Size::new(Abs::pt(400.0), Abs::pt(100.0));
+	assert_eq!(determine_pixels_per_point(normal).unwrap(), 5.0);
+
+	let oversized = Size::new(Abs::pt(100.0), Abs::pt(20000.0));
+	let error = determine_pixels_per_point(oversized).unwrap_err();
+	assert_eq!((error.x, error.y), (100.0, 20000.0));
+
+	let doubly_oversized = Size::new(Abs::pt(30000.0), Abs::pt(20000.0));
+	let error = determine_pixels_per_point(doubly_oversized)
+		.unwrap_err()
+		.to_string();
+	assert!(error.contains("30000 pt by 20000 pt"), "{error}");
+}
+
+/// Rounds the scale up to a whole number so glyph edges align with the pixel grid,
+/// since the rasterizer does not expose hinting.
+///
+/// Once the scale is at least one, rounding up at most doubles each axis. The automatic scale only
+/// gets there for pages with an area of at most `DESIRED_RESOLUTION` squared points, and
+/// `ensure_min_long_edge` for long edges of at most `MAX_MIN_SIZE` pixels, so the result stays
+/// small. Below one there is no such bound (a scale of 0.1 would render a hundred times as many
+/// pixels), so those scales are left alone.
+///
+/// This is not capped at `MAX_PIXELS_PER_POINT`, so it can be applied after `ensure_min_long_edge`.
+fn snap_pixels_per_point(pixels_per_point: f32) -> f32 {
+	if pixels_per_point < 1.0 {
+		pixels_per_point
+	} else {
+		pixels_per_point.ceil()
+	}
+}
+
+#[test]
+fn test_snap_pixels_per_point() {
+	assert_eq!(snap_pixels_per_point(0.5), 0.5);
+	assert_eq!(snap_pixels_per_point(1.0), 1.0);
+	assert_eq!(snap_pixels_per_point(1.2), 2.0);
+	assert_eq!(snap_pixels_per_point(4.9), 5.0);
+	assert_eq!(snap_pixels_per_point(13.3), 14.0);
+	assert_eq!(
+		snap_pixels_per_point(MAX_PIXELS_PER_POINT),
+		MAX_PIXELS_PER_POINT
+	);
+}
+
+/// Raises the scale so the longer side of the page is rendered with at least `min_long_edge` pixels.
+fn ensure_min_long_edge(pixels_per_point: f32, size: Size, min_long_edge: u32) -> f32 {
+	let long_edge = size.x.max(size.y).to_pt() as f32;
+	if long_edge <= 0.0 {
+		return pixels_per_point;
+	}
+
+	let required = min_long_edge.min(MAX_MIN_SIZE) as f32 / long_edge;
+	pixels_per_point.max(required)
+}
+
+#[test]
+fn test_ensure_min_long_edge() {
+	let size = Size::new(Abs::pt(20.0), Abs::pt(10.0));
+	assert_eq!(ensure_min_long_edge(5.0, size, 400), 20.0);
+	assert_eq!(ensure_min_long_edge(5.0, size, 50), 5.0);
+	assert_eq!(ensure_min_long_edge(5.0, size, 100_000), 100.0);
+	assert_eq!(ensure_min_long_edge(5.0, Size::zero(), 400), 5.0);
+}
+
+/// The color the padding should have to extend the page background.
+///
+/// Only solid fills can be extended; anything else is padded with transparency.
+fn padding_color(fill: Option<Paint>) -> Option<PremultipliedColorU8> {
+	match fill {
+		Some(Paint::Solid(color)) => {
+			let [r, g, b, a] = color.to_rgb().to_vec4_u8();
+			Some(ColorU8::from_rgba(r, g, b, a).premultiply())
+		}
+		_ => None,
+	}
+}
+
+#[test]
+fn test_padding_color() {
+	let fill = Paint::Solid(typst::visualize::Color::from_u8(0, 0, 255, 255));
+	assert_eq!(
+		padding_color(Some(fill)),
+		PremultipliedColorU8::from_rgba(0, 0, 255, 255)
+	);
+	assert_eq!(padding_color(None), None);
+}
+
+/// Centers the pixmap on a canvas of at least the given size.
+///
+/// The padding is filled with `background`, or left transparent if there is none.
+/// Fails if the canvas would have more than `MAX_PIXELS` pixels.
+fn pad_to_min_size(
+	pixmap: Pixmap,
+	(min_width, min_height): (u32, u32),
+	background: Option<PremultipliedColorU8>,
+) -> Result<Pixmap, TooManyPixels> {
+	let width = pixmap.width().max(min_width.min(MAX_MIN_SIZE));
+	let height = pixmap.height().max(min_height.min(MAX_MIN_SIZE));
+	if width == pixmap.width() && height == pixmap.height() {
+		return Ok(pixmap);
+	}
+
+	let pixels = width as f32 * height as f32;
+	if pixels > MAX_PIXELS {
+		return Err(TooManyPixels { pixels });
+	}
+
+	// The unwrap will never fail since the pixel count is bounded and each side is either that of
+	// the existing pixmap or at most `MAX_MIN_SIZE`.
+	let mut padded = Pixmap::new(width, height).unwrap();
+	if let Some(background) = background {
+		padded.pixels_mut().fill(background);
+	}
+
+	let x = ((width - pixmap.width()) / 2) as usize;
+	let y = ((height - pixmap.height()) / 2) as usize;
+	let rows = pixmap.pixels().chunks_exact(pixmap.width() as usize);
+	for (row_index, row) in rows.enumerate() {
+		let start = (y + row_index) * width as usize + x;
+		padded.pixels_mut()[start..start + row.len()].copy_from_slice(row);
+	}
+
+	Ok(padded)
+}
+
+#[test]
+fn test_pad_to_min_size() {
+	let background = PremultipliedColorU8::from_rgba(0, 0, 255, 255).unwrap();
+	let content = PremultipliedColorU8::from_rgba(255, 0, 0, 255).unwrap();
+	let mut pixmap = Pixmap::new(3, 3).unwrap();
+	pixmap.pixels_mut().fill(background);
+	pixmap.pixels_mut()[4] = content;
+
+	let padded = pad_to_min_size(pixmap.clone(), (9, 2), Some(background)).unwrap();
+	assert_eq!((padded.width(), padded.height()), (9, 3));
+	assert_eq!(padded.pixel(4, 1), Some(content));
+	assert_eq!(padded.pixel(0, 1), Some(background));
+	assert_eq!(padded.pixel(8, 1), Some(background));
+
+	let unchanged = pad_to_min_size(pixmap, (1, 1), Some(background)).unwrap();
+	assert_eq!((unchanged.width(), unchanged.height()), (3, 3));
+
+	// Content in the corner must not leak into the padding.
+	let mut pixmap = Pixmap::new(3, 3).unwrap();
+	pixmap.pixels_mut().fill(background);
+	pixmap.pixels_mut()[0] = content;
+	let padded = pad_to_min_size(pixmap.clone(), (5, 3), Some(background)).unwrap();
+	assert_eq!(padded.pixel(1, 0), Some(content));
+	assert_eq!(padded.pixel(0, 0), Some(background));
+	assert_eq!(padded.pixel(4, 0), Some(background));
+
+	let padded = pad_to_min_size(pixmap, (5, 3), None).unwrap();
+	assert_eq!(padded.pixel(0, 0), Some(PremultipliedColorU8::TRANSPARENT));
+
+	// A wide strip padded to a tall canvas would multiply its pixel count.
+	let strip = Pixmap::new(50_000, 1).unwrap();
+	let error = pad_to_min_size(strip, (MAX_MIN_SIZE, MAX_MIN_SIZE), None).unwrap_err();
+	assert_eq!(error.pixels, 50_000.0 * MAX_MIN_SIZE as f32);
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(
+	"rendered output was too big: it would have {pixels} pixels but the maximum is {MAX_PIXELS}"
+)]
+pub struct TooManyPixels {
+	pixels: f32,
+}
+
+/// Converts a fixed DPI into a scale.
+fn fixed_pixels_per_point(size: Size, dpi: f32) -> Result<f32, TooManyPixels> {
+	#![allow(clippy::cast_possible_truncation)]
+
+	let pixels_per_point = dpi / POINTS_PER_INCH;
+	let pixels = size.x.to_pt() as f32 * size.y.to_pt() as f32 * pixels_per_point.powi(2);
+	if pixels > MAX_PIXELS {
+		Err(TooManyPixels { pixels })
+	} else {
+		Ok(pixels_per_point)
+	}
+}
+
+#[test]
+fn test_fixed_pixels_per_point() {
+	#![allow(clippy::cast_possible_truncation)]
+
+	let a5 = Size::new(Abs::mm(148.0), Abs::mm(210.0));
+	let pixels_per_point = fixed_pixels_per_point(a5, 300.0).unwrap();
+	let width = (a5.x.to_pt() as f32 * pixels_per_point).round();
+	let height = (a5.y.to_pt() as f32 * pixels_per_point).round();
+	assert!((width - 1748.0).abs() <= 1.0, "{width}");
+	assert!((height - 2480.0).abs() <= 1.0, "{height}");
+
+	let a0 = Size::new(Abs::mm(841.0), Abs::mm(1189.0));
+	assert!(fixed_pixels_per_point(a0, 300.0).is_err());
+
+	let native = fixed_pixels_per_point(a5, POINTS_PER_INCH).unwrap();
+	assert_eq!(native, 1.0);
+	assert_eq!(
+		(a5.x.to_pt() as f32 * native).round(),
+		a5.x.to_pt().round() as f32
+	);
+}
+
+fn choose_pixels_per_point(size: Size, options: &RenderOptions) -> Result<f32, String> {
+	// This also rejects pages that are too big, even if a fixed DPI is used.
+	let mut pixels_per_point = determine_pixels_per_point(size).map_err(to_string)?;
+
+	if let Some(dpi) = options.dpi {
+		return fixed_pixels_per_point(size, dpi).map_err(to_string);
+	}
+
+	if let Some(min_long_edge) = options.min_long_edge {
+		pixels_per_point = ensure_min_long_edge(pixels_per_point, size, min_long_edge);
+	}
+	// Snap last so that the final scale is a whole number.
+	if options.snap_scale {
+		pixels_per_point = snap_pixels_per_point(pixels_per_point);
+	}
+
+	Ok(pixels_per_point)
+}
+
+#[test]
+fn test_choose_pixels_per_point_snap_and_min_long_edge() {
+	let size = Size::new(Abs::pt(30.0), Abs::pt(20.0));
+	let options = RenderOptions {
+		snap_scale: true,
+		min_long_edge: Some(400),
+		..Default::default()
+	};
+	// 400 / 30 is about 13.3, which is then rounded up.
+	assert_eq!(choose_pixels_per_point(size, &options).unwrap(), 14.0);
+
+	let options = RenderOptions {
+		snap_scale: false,
+		..options
+	};
+	assert_eq!(
+		choose_pixels_per_point(size, &options).unwrap(),
+		400.0 / 30.0
+	);
+}
+
 fn to_string(v: impl ToString) -> String {
 	v.to_string()
 }
 
+fn is_unknown_font(warning: &SourceDiagnostic) -> bool {
+	warning.message.starts_with("unknown font family")
+}
+
+fn encode_png(mut pixmap: Pixmap) -> Vec<u8> {
+	// `tiny-skia` stores premultiplied alpha, but PNG uses straight alpha.
+	// Convert in place, since a copy of a large page would double the memory used.
+	for pixel in pixmap.data_mut().chunks_exact_mut(4) {
+		// The unwrap will never fail since the pixmap only holds valid premultiplied colors.
+		let color = PremultipliedColorU8::from_rgba(pixel[0], pixel[1], pixel[2], pixel[3])
+			.unwrap()
+			.demultiply();
+		pixel.copy_from_slice(&[color.red(), color.green(), color.blue(), color.alpha()]);
+	}
+
+	let mut writer = Cursor::new(Vec::new());
+
+	// The unwrap will never fail since `Vec`'s `Write` implementation is infallible.
+	image::write_buffer_with_format(
+		&mut writer,
+		pixmap.data(),
+		pixmap.width(),
+		pixmap.height(),
+		image::ColorType::Rgba8,
+		image::ImageFormat::Png,
+	)
+	.unwrap();
+
+	writer.into_inner()
+}
+
+#[test]
+fn test_encode_png() {
+	let mut pixmap = Pixmap::new(2, 1).unwrap();
+	pixmap.pixels_mut()[0] = tiny_skia::ColorU8::from_rgba(255, 0, 0, 255).premultiply();
+	pixmap.pixels_mut()[1] = tiny_skia::ColorU8::from_rgba(0, 0, 255, 128).premultiply();
+
+	let png = encode_png(pixmap);
+	let decoded = image::load_from_memory_with_format(&png, image::ImageFormat::Png)
+		.unwrap()
+		.to_rgba8();
+	assert_eq!(decoded.get_pixel(0, 0).0, [255, 0, 0, 255]);
+	assert_eq!(decoded.get_pixel(1, 0).0, [0, 0, 255, 128]);
+}
+
 const PAGE_LIMIT: usize = 5;
 const BYTES_LIMIT: usize = 25 * 1024 * 1024;
+const SOURCE_BYTES_LIMIT: usize = 64 * 1024;
+
+/// The number of pages after the rendered ones.
+fn count_more_pages(total: usize, first_page: usize, rendered: usize) -> usize {
+	total.saturating_sub(first_page.saturating_add(rendered))
+}
+
+#[test]
+fn test_count_more_pages() {
+	assert_eq!(count_more_pages(4, 0, 4), 0);
+	assert_eq!(count_more_pages(4, 0, 1), 3);
+	assert_eq!(count_more_pages(4, 1, 1), 2);
+	assert_eq!(count_more_pages(4, 10, 0), 0);
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("source was too long: it was {len} bytes but the maximum is {max}")]
+pub struct SourceTooLong {
+	len: usize,
+	max: usize,
+}
+
+fn check_source_length(source: &str, max: usize) -> Result<(), SourceTooLong> {
+	let len = source.len();
+	if len > max {
+		Err(SourceTooLong { len, max })
+	} else {
+		Ok(())
+	}
+}
+
+#[test]
+fn test_check_source_length() {
+	assert!(check_source_length("abc", 3).is_ok());
+	let error = check_source_length("abcd", 3).unwrap_err();
+	assert_eq!((error.len, error.max), (4, 3));
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(
+	"page {} requested but the document has {pages} page{}",
+	.first_page + 1,
+	if *.pages == 1 { "" } else { "s" }
+)]
+pub struct PageOutOfRange {
+	first_page: usize,
+	pages: usize,
+}
+
+/// Rejects a first page beyond the end of the document.
+///
+/// Empty documents are let through so that they still report that no pages were generated.
+fn check_first_page(pages: usize, first_page: usize) -> Result<(), PageOutOfRange> {
+	if pages > 0 && first_page >= pages {
+		Err(PageOutOfRange { first_page, pages })
+	} else {
+		Ok(())
+	}
+}
+
+#[test]
+fn test_check_first_page() {
+	assert!(check_first_page(3, 0).is_ok());
+	assert!(check_first_page(3, 2).is_ok());
+	assert!(check_first_page(0, 4).is_ok());
+	assert_eq!(
+		check_first_page(3, 8).unwrap_err().to_string(),
+		"page 9 requested but the document has 3 pages",
+	);
+	assert_eq!(
+		check_first_page(1, 1).unwrap_err().to_string(),
+		"page 2 requested but the document has 1 page",
+	);
+}
+
+pub fn render(
+	sandbox: &Sandbox,
+	source: String,
+	options: &RenderOptions,
+) -> Result<Rendered, String> {
+	check_source_length(&source, SOURCE_BYTES_LIMIT).map_err(to_string)?;
 
-pub fn render(sandbox: &Sandbox, source: String) -> Result<Rendered, String> {
 	let world = sandbox.with_source(source);
 
 	let document = typst::compile(&world);
@@ -59,30 +430,38 @@ pub fn render(sandbox: &Sandbox, source: String) -> Result<Rendered, String> {
 		.output
 		.map_err(|diags| format_diagnostics(&world, &diags))?;
 
+	let promoted = warnings
+		.iter()
+		.filter(|warning| {
+			options.warnings_as_errors || (options.strict_fonts && is_unknown_font(warning))
+		})
+		.map(|warning| SourceDiagnostic {
+			severity: Severity::Error,
+			..warning.clone()
+		})
+		.collect::<Vec<_>>();
+	if !promoted.is_empty() {
+		return Err(format_diagnostics(&world, &promoted));
+	}
+
+	check_first_page(document.pages.len(), options.first_page).map_err(to_string)?;
+
 	let mut total_attachment_size = 0;
 
 	let images = document
 		.pages
 		.iter()
+		.skip(options.first_page)
 		.take(PAGE_LIMIT)
 		.map(|page| {
-			let pixels_per_point = determine_pixels_per_point(page.frame.size()).map_err(to_string)?;
-			let pixmap = typst_render::render(page, pixels_per_point);
-
-			let mut writer = Cursor::new(Vec::new());
-
-			// The unwrap will never fail since `Vec`'s `Write` implementation is infallible.
-			image::write_buffer_with_format(
-				&mut writer,
-				bytemuck::cast_slice(pixmap.pixels()),
-				pixmap.width(),
-				pixmap.height(),
-				image::ColorType::Rgba8,
-				image::ImageFormat::Png,
-			)
-			.unwrap();
-
-			Ok(writer.into_inner())
+			let pixels_per_point = choose_pixels_per_point(page.frame.size(), options)?;
+			let mut pixmap = typst_render::render(page, pixels_per_point);
+			if let Some(min_size) = options.min_size {
+				pixmap = pad_to_min_size(pixmap, min_size, padding_color(page.fill_or_white()))
+					.map_err(to_string)?;
+			}
+
+			Ok(encode_png(pixmap))
 		})
 		.take_while(|image| {
 			if let Ok(image) = image {
@@ -94,7 +473,7 @@ pub fn render(sandbox: &Sandbox, source: String) -> Result<Rendered, String> {
 		})
 		.collect::<Result<Vec<_>, String>>()?;
 
-	let more_pages = document.pages.len() - images.len();
+	let more_pages = count_more_pages(document.pages.len(), options.first_page, images.len());
 
 	Ok(Rendered {
 		images,
@@ -102,3 +481,95 @@ pub fn render(sandbox: &Sandbox, source: String) -> Result<Rendered, String> {
 		warnings: format_diagnostics(&world, &warnings),
 	})
 }
+
+#[test]
+fn test_strict_fonts() {
+	let sandbox = crate::sandbox::test_sandbox();
+	let source = "#set text(font: \"Bogus\")\nhello";
+
+	let lenient = render(&sandbox, source.into(), &RenderOptions::default()).unwrap();
+	assert!(lenient.warnings.contains("unknown font family"));
+
+	let options = RenderOptions {
+		strict_fonts: true,
+		..Default::default()
+	};
+	let error = render(&sandbox, source.into(), &options).unwrap_err();
+	assert!(error.contains("unknown font family"), "{error}");
+}
+
+#[test]
+fn test_warnings_as_errors() {
+	let sandbox = crate::sandbox::test_sandbox();
+	let source = "**";
+
+	let lenient = render(&sandbox, source.into(), &RenderOptions::default()).unwrap();
+	assert!(lenient.warnings.contains("no text within stars"));
+
+	let options = RenderOptions {
+		warnings_as_errors: true,
+		..Default::default()
+	};
+	let error = render(&sandbox, source.into(), &options).unwrap_err();
+	assert!(error.contains("no text within stars"), "{error}");
+	assert!(!error.contains("Warning"), "{error}");
+}
+
+#[test]
+fn test_min_size() {
+	let sandbox = crate::sandbox::test_sandbox();
+	// The page is exactly the block: 10 pt square, so 50 px at the maximum scale.
+	let source = "#set page(width: auto, height: auto, margin: 0pt)\n\
+		#block(width: 10pt, height: 10pt, fill: black)";
+	let options = RenderOptions {
+		min_size: Some((200, 200)),
+		..Default::default()
+	};
+
+	let rendered = render(&sandbox, source.into(), &options).unwrap();
+	let image = image::load_from_memory_with_format(&rendered.images[0], image::ImageFormat::Png)
+		.unwrap()
+		.to_rgba8();
+	assert!(image.width() >= 200 && image.height() >= 200);
+
+	let content = image
+		.enumerate_pixels()
+		.filter(|(_, _, pixel)| pixel.0[0] < 128)
+		.map(|(x, y, _)| (x, y))
+		.collect::<Vec<_>>();
+	assert!(!content.is_empty());
+	let left = content.iter().map(|&(x, _)| x).min().unwrap();
+	let right = content.iter().map(|&(x, _)| x).max().unwrap();
+	let top = content.iter().map(|&(_, y)| y).min().unwrap();
+	let bottom = content.iter().map(|&(_, y)| y).max().unwrap();
+	assert_eq!(left, image.width() - 1 - right);
+	assert_eq!(top, image.height() - 1 - bottom);
+	// The padding extends the page's white background.
+	assert_eq!(image.get_pixel(0, 0).0, [255, 255, 255, 255]);
+}
+
+#[test]
+fn test_snap_scale_render() {
+	let sandbox = crate::sandbox::test_sandbox();
+	// A 1 pt rule, like a fraction bar, on a page that gets a scale of 2.5 by default.
+	let source = "#set page(width: 400pt, height: 400pt, margin: 0pt)\n\
+		#place(dx: 10pt, dy: 10pt, rect(width: 20pt, height: 1pt, fill: black, stroke: none))";
+	let partial_pixels = |snap_scale: bool| {
+		let options = RenderOptions {
+			snap_scale,
+			..Default::default()
+		};
+		let rendered = render(&sandbox, source.into(), &options).unwrap();
+		image::load_from_memory_with_format(&rendered.images[0], image::ImageFormat::Png)
+			.unwrap()
+			.to_rgba8()
+			.pixels()
+			.filter(|pixel| (1..255).contains(&pixel.0[0]))
+			.count()
+	};
+
+	let blurry = partial_pixels(false);
+	let sharp = partial_pixels(true);
+	assert!(blurry > 0);
+	assert!(sharp < blurry, "{sharp} >= {blurry}");
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::{Mutex, Once, PoisonError};
 
 use typst::diag::{eco_format, FileError, FileResult, PackageError, PackageResult};
 use typst::foundations::{Bytes, Datetime};
@@ -23,9 +23,7 @@ impl FileEntry {
 			source
 		} else {
 			let contents = std::str::from_utf8(&self.bytes).map_err(|_| FileError::InvalidUtf8)?;
-			// Defuse the BOM!
-			let contents = contents.trim_start_matches('\u{feff}');
-			let source = Source::new(id, contents.into());
+			let source = Source::new(id, strip_bom(contents).into());
 			self.source.insert(source)
 		};
 		Ok(source.clone())
@@ -40,6 +38,7 @@ pub struct Sandbox {
 	cache_directory: PathBuf,
 	http: ureq::Agent,
 	files: Mutex<HashMap<FileId, FileEntry>>,
+	warmed: Once,
 }
 
 fn fonts() -> Vec<Font> {
@@ -54,7 +53,18 @@ fn fonts() -> Vec<Font> {
 		.collect()
 }
 
+/// Defuses the BOM, so spans are relative to the text that is shown in reports.
+fn strip_bom(text: &str) -> &str {
+	text.trim_start_matches('\u{feff}')
+}
+
 fn make_source(source: String) -> Source {
+	let stripped = strip_bom(&source);
+	let source = if stripped.len() == source.len() {
+		source
+	} else {
+		stripped.to_owned()
+	};
 	Source::detached(source)
 }
 
@@ -95,6 +105,7 @@ impl Sandbox {
 				.into(),
 			http: ureq::Agent::new(),
 			files: Mutex::new(HashMap::new()),
+			warmed: Once::new(),
 		}
 	}
 
@@ -106,6 +117,31 @@ impl Sandbox {
 		}
 	}
 
+	/// Compiles a trivial document so lazily-initialized state, such as the font book's hash,
+	/// is ready before the first real render.
+	/// Only the first call does any work.
+	pub fn warm(&self) {
+		self.warmed.call_once(|| {
+			let world = self.with_source("warm".into());
+			_ = typst::compile(&world);
+		});
+	}
+
+	/// Clears all transient state: `comemo`'s memoization and the in-memory file cache.
+	///
+	/// Fonts and the standard library are kept, as are packages unpacked into the cache directory;
+	/// their files are read from disk again on next use.
+	/// This also clears poisoning of the file cache after a panic.
+	pub fn reset(&self) {
+		comemo::evict(0);
+		self
+			.files
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.clear();
+		self.files.clear_poison();
+	}
+
 	/// Returns the system path of the unpacked package.
 	fn ensure_package(&self, package: &PackageSpec) -> PackageResult<PathBuf> {
 		let package_subdir = format!("{}/{}/{}", package.namespace, package.name, package.version);
@@ -186,6 +222,71 @@ impl Sandbox {
 	}
 }
 
+#[cfg(test)]
+pub(crate) fn test_sandbox() -> Sandbox {
+	std::env::set_var("CACHE_DIRECTORY", std::env::temp_dir());
+	Sandbox::new()
+}
+
+#[test]
+fn test_warm() {
+	let sandbox = test_sandbox();
+	sandbox.warm();
+	sandbox.warm();
+	let world = sandbox.with_source("hello".into());
+	assert!(typst::compile(&world).output.is_ok());
+}
+
+#[test]
+fn test_reset() {
+	let sandbox = test_sandbox();
+	let id = FileId::new(None, typst::syntax::VirtualPath::new("cached.typ"));
+	sandbox.files.lock().unwrap().insert(
+		id,
+		FileEntry {
+			bytes: Bytes::from_static(b"cached"),
+			source: None,
+		},
+	);
+
+	sandbox.reset();
+
+	assert!(sandbox.files.lock().unwrap().is_empty());
+	let world = sandbox.with_source("= Hello".into());
+	assert!(typst::compile(&world).output.is_ok());
+}
+
+#[test]
+fn test_bom_and_crlf() {
+	let sandbox = test_sandbox();
+	let world = sandbox.with_source("\u{feff}a\r\n#b".into());
+	assert_eq!(world.main_source().text(), "a\r\n#b");
+	let doubled = sandbox.with_source("\u{feff}\u{feff}a".into());
+	assert_eq!(doubled.main_source().text(), "a");
+
+	let diagnostics = typst::compile(&world).output.unwrap_err();
+	let report = crate::diagnostic::format_diagnostics(&world, &diagnostics);
+	assert!(report.contains("main.typ:2:2"), "{report}");
+}
+
+#[test]
+fn test_error_in_included_file() {
+	let sandbox = test_sandbox();
+	let id = FileId::new(None, typst::syntax::VirtualPath::new("other.typ"));
+	sandbox.files.lock().unwrap().insert(
+		id,
+		FileEntry {
+			bytes: Bytes::from_static(b"a\n#b"),
+			source: None,
+		},
+	);
+
+	let world = sandbox.with_source("#include \"other.typ\"".into());
+	let diagnostics = typst::compile(&world).output.unwrap_err();
+	let report = crate::diagnostic::format_diagnostics(&world, &diagnostics);
+	assert!(report.contains("other.typ:2:2"), "{report}");
+}
+
 impl WithSource<'_> {
 	pub fn main_source(&self) -> &Source {
 		&self.source